@@ -1,55 +1,225 @@
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use std::process::Command;
 use std::sync::Mutex;
 use std::path::{Path, PathBuf};
 
-struct BackendProcess(Mutex<Option<std::process::Child>>);
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+// Gives the backend its own console process group; its descendants are
+// taken down by taskkill /T in kill_backend_process_tree.
+#[cfg(windows)]
+const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+
+// Spawned either as a Tauri sidecar (bundled release builds) or as a
+// plain child process (dev/PATH fallback).
+enum BackendHandle {
+    Plain(std::process::Child),
+    Sidecar(tauri_plugin_shell::process::CommandChild),
+}
+
+struct BackendProcess(Mutex<Option<BackendHandle>>);
+
+// The ephemeral port the backend was bound to for this run.
+struct BackendPort(u16);
 
 #[tauri::command]
-fn notify_native(title: String, body: String) -> Result<(), String> {
-    #[cfg(target_os = "macos")]
+fn backend_port(state: tauri::State<BackendPort>) -> u16 {
+    state.0
+}
+
+// Reveals the app's log folder in the OS file manager.
+#[tauri::command]
+fn open_log_location(app: tauri::AppHandle) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+    let log_dir = app.path().app_log_dir().map_err(|e| e.to_string())?;
+    app.opener()
+        .reveal_item_in_dir(log_dir)
+        .map_err(|e| e.to_string())
+}
+
+// Binds an OS-assigned free port and releases it, leaving a short window
+// for the backend to bind the same port in turn.
+fn reserve_ephemeral_port() -> std::io::Result<u16> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    listener.local_addr().map(|addr| addr.port())
+}
+
+// Puts the backend in its own session/process group so it can be killed
+// as a unit instead of leaving orphaned uvicorn reloader children behind.
+fn detach_process_group(cmd: &mut Command) -> &mut Command {
+    #[cfg(unix)]
     {
-        let esc = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
-        let script = format!(
-            "display notification \"{}\" with title \"{}\"",
-            esc(&body),
-            esc(&title)
-        );
-        Command::new("osascript")
-            .args(["-e", &script])
-            .status()
-            .map_err(|e| e.to_string())?;
-        return Ok(());
+        // SAFETY: setsid() is async-signal-safe and only affects the
+        // child process after fork, before exec.
+        unsafe {
+            cmd.pre_exec(|| {
+                libc::setsid();
+                Ok(())
+            });
+        }
     }
+    #[cfg(windows)]
+    {
+        cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+    cmd
+}
 
-    #[cfg(target_os = "linux")]
+// Gives the backend a grace period to shut down before forcing it.
+// Blocks for up to that grace period, so callers on the UI thread must
+// run this on a background thread.
+fn kill_backend_process_tree(handle: BackendHandle) {
+    // Both variants give us a raw PID, which the signal/taskkill path below
+    // works from, so Plain and Sidecar share the same kill sequence.
+    let pid = match &handle {
+        BackendHandle::Plain(child) => child.id(),
+        BackendHandle::Sidecar(child) => child.pid(),
+    };
+
+    #[cfg(unix)]
     {
-        Command::new("notify-send")
-            .args([&title, &body])
-            .status()
-            .map_err(|e| e.to_string())?;
-        return Ok(());
+        // A negative PID targets the whole process group that setsid() gave a
+        // Plain child. A sidecar is not detached into a group of its own, so
+        // only its own PID is signalled; its descendants are not.
+        let target = match &handle {
+            BackendHandle::Plain(_) => -(pid as libc::pid_t),
+            BackendHandle::Sidecar(_) => pid as libc::pid_t,
+        };
+        unsafe {
+            libc::kill(target, libc::SIGTERM);
+        }
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(3);
+        let mut exited = false;
+        while std::time::Instant::now() < deadline {
+            // Signal 0 sends nothing; it just probes whether pid still exists.
+            if unsafe { libc::kill(pid as libc::pid_t, 0) } != 0 {
+                exited = true;
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        if !exited {
+            unsafe {
+                libc::kill(target, libc::SIGKILL);
+            }
+        }
     }
 
-    #[cfg(target_os = "windows")]
+    #[cfg(windows)]
     {
-        let ps = format!(
-            "$t='{}';$b='{}';[Windows.UI.Notifications.ToastNotificationManager,Windows.UI.Notifications,ContentType=WindowsRuntime]>$null;[Windows.Data.Xml.Dom.XmlDocument,Windows.Data.Xml.Dom.XmlDocument,ContentType=WindowsRuntime]>$null;$x=New-Object Windows.Data.Xml.Dom.XmlDocument;$x.LoadXml(\"<toast><visual><binding template='ToastGeneric'><text>$t</text><text>$b</text></binding></visual></toast>\");$n=[Windows.UI.Notifications.ToastNotification]::new($x);[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('SEFS').Show($n);",
-            title.replace('\'', "''"),
-            body.replace('\'', "''")
-        );
-        Command::new("powershell")
-            .args(["-NoProfile", "-Command", &ps])
-            .status()
-            .map_err(|e| e.to_string())?;
-        return Ok(());
+        let _ = Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .status();
     }
 
-    #[allow(unreachable_code)]
-    Err("Unsupported platform".to_string())
+    match handle {
+        BackendHandle::Plain(mut child) => {
+            let _ = child.wait();
+        }
+        #[cfg(not(any(unix, windows)))]
+        BackendHandle::Sidecar(child) => {
+            let _ = child.kill();
+        }
+        #[cfg(any(unix, windows))]
+        BackendHandle::Sidecar(_) => {}
+    }
 }
 
-fn spawn_backend(backend_path: &std::path::Path) -> Option<std::process::Child> {
+// One action button on a native notification, surfaced back to the
+// webview as part of a notification://action event when clicked.
+#[derive(serde::Deserialize)]
+struct NotificationAction {
+    id: String,
+    label: String,
+}
+
+// Payload for notification://action: which notification was clicked, and
+// which action button if any (None means the notification body itself was
+// clicked), so the webview can tell clicks apart and route them.
+#[derive(Clone, serde::Serialize)]
+struct NotificationActionEvent {
+    notification_id: i32,
+    action_id: Option<String>,
+}
+
+// Shows a native notification through tauri-plugin-notification rather
+// than shelling out to osascript/notify-send/PowerShell per platform.
+// Kept under the old notify_native name for backward compatibility.
+#[tauri::command]
+fn notify_native(
+    app: tauri::AppHandle,
+    title: String,
+    body: String,
+    icon: Option<String>,
+    tag: Option<String>,
+    actions: Option<Vec<NotificationAction>>,
+) -> Result<(), String> {
+    use tauri_plugin_notification::NotificationExt;
+
+    let mut builder = app.notification().builder().title(title).body(body);
+    if let Some(icon) = icon {
+        builder = builder.icon(icon);
+    }
+    if let Some(tag) = tag {
+        builder = builder.tag(tag);
+    }
+    if let Some(actions) = actions {
+        for action in actions {
+            builder = builder.action(action.id, action.label);
+        }
+    }
+    builder.show().map_err(|e| e.to_string())
+}
+
+// bundle.externalBin name for the frozen backend; the entry and its
+// packaging step live in tauri.conf.json and the release build script.
+const SIDECAR_BACKEND_BIN: &str = "krith-backend";
+
+fn spawn_sidecar_backend(app: &tauri::AppHandle, backend_path: &Path, port: u16) -> Option<BackendHandle> {
+    use tauri_plugin_shell::ShellExt;
+
+    let cmd = match app.shell().sidecar(SIDECAR_BACKEND_BIN) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            log::warn!("Sidecar backend {:?} not available: {}", SIDECAR_BACKEND_BIN, e);
+            return None;
+        }
+    };
+
+    let cmd = cmd.args(["--host", "0.0.0.0", "--port", &port.to_string()]);
+    // A frozen sidecar binary ships with no accompanying app/ tree to cd
+    // into, so only set a working directory when one actually exists.
+    let cmd = if backend_path.is_dir() {
+        cmd.current_dir(backend_path)
+    } else {
+        cmd
+    };
+
+    match cmd.spawn() {
+        Ok((_events, child)) => {
+            log::info!("Backend started via sidecar (PID: {})", child.pid());
+            Some(BackendHandle::Sidecar(child))
+        }
+        Err(e) => {
+            log::warn!("Sidecar backend failed to start: {}", e);
+            None
+        }
+    }
+}
+
+fn spawn_backend(app: &tauri::AppHandle, backend_path: &std::path::Path, port: u16) -> Option<BackendHandle> {
+    let port_arg = port.to_string();
+
+    if !cfg!(debug_assertions) {
+        if let Some(handle) = spawn_sidecar_backend(app, backend_path, port) {
+            return Some(handle);
+        }
+    }
+
     if !backend_path.exists() {
         log::warn!("Backend path does not exist: {:?}", backend_path);
         return None;
@@ -65,14 +235,13 @@ fn spawn_backend(backend_path: &std::path::Path) -> Option<std::process::Child>
     // Try uv from PATH + common install locations.
     let uv_candidates = ["uv", "/opt/homebrew/bin/uv", "/usr/local/bin/uv", "/usr/bin/uv"];
     for uv_bin in uv_candidates {
-        match Command::new(uv_bin)
-            .args(["run", "uvicorn", "app.main:app", "--host", "0.0.0.0", "--port", "8484"])
-            .current_dir(backend_path)
-            .spawn()
-        {
+        let mut cmd = Command::new(uv_bin);
+        cmd.args(["run", "uvicorn", "app.main:app", "--host", "0.0.0.0", "--port", &port_arg])
+            .current_dir(backend_path);
+        match detach_process_group(&mut cmd).spawn() {
             Ok(child) => {
                 log::info!("Backend started via {} (PID: {})", uv_bin, child.id());
-                return Some(child);
+                return Some(BackendHandle::Plain(child));
             }
             Err(e) => {
                 log::warn!("{} failed: {}", uv_bin, e);
@@ -83,14 +252,13 @@ fn spawn_backend(backend_path: &std::path::Path) -> Option<std::process::Child>
     // Fallback to python3 from PATH + common macOS locations.
     let py_candidates = ["python3", "/usr/bin/python3", "/opt/homebrew/bin/python3", "/usr/local/bin/python3"];
     for py_bin in py_candidates {
-        match Command::new(py_bin)
-            .args(["-m", "uvicorn", "app.main:app", "--host", "0.0.0.0", "--port", "8484"])
-            .current_dir(backend_path)
-            .spawn()
-        {
+        let mut cmd = Command::new(py_bin);
+        cmd.args(["-m", "uvicorn", "app.main:app", "--host", "0.0.0.0", "--port", &port_arg])
+            .current_dir(backend_path);
+        match detach_process_group(&mut cmd).spawn() {
             Ok(child) => {
                 log::info!("Backend started via {} (PID: {})", py_bin, child.id());
-                return Some(child);
+                return Some(BackendHandle::Plain(child));
             }
             Err(e) => {
                 log::warn!("{} failed: {}", py_bin, e);
@@ -150,42 +318,172 @@ fn resolve_release_backend_path(resource_dir: &Path) -> PathBuf {
     resource_dir.join("backend")
 }
 
-fn wait_for_backend_ready() {
-    let url = "http://localhost:8484/api/status";
+// Payload for the backend://status event; drives the webview's splash
+// screen and reconnect logic.
+#[derive(Clone, serde::Serialize)]
+struct BackendStatusEvent {
+    state: &'static str, // "starting" | "ready" | "unhealthy" | "failed"
+    attempt: u32,
+    elapsed_ms: u64,
+}
+
+fn emit_backend_status(app: &tauri::AppHandle, state: &'static str, attempt: u32, elapsed: std::time::Duration) {
+    let _ = app.emit(
+        "backend://status",
+        BackendStatusEvent {
+            state,
+            attempt,
+            elapsed_ms: elapsed.as_millis() as u64,
+        },
+    );
+}
+
+const STARTUP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+const BACKOFF_START: std::time::Duration = std::time::Duration::from_millis(250);
+const BACKOFF_CAP: std::time::Duration = std::time::Duration::from_secs(5);
+const HEALTH_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+const UNHEALTHY_THRESHOLD: u32 = 3;
+const RESTART_BACKOFF_CAP: std::time::Duration = std::time::Duration::from_secs(30);
+
+// Polls /api/status with exponential backoff and jitter, emitting
+// backend://status events. Returns false on STARTUP_TIMEOUT.
+fn poll_until_ready(app: &tauri::AppHandle, client: &reqwest::blocking::Client, url: &str, start: std::time::Instant) -> bool {
+    let mut attempt = 0u32;
+    let mut delay = BACKOFF_START;
+    loop {
+        attempt += 1;
+        match client.get(url).send() {
+            Ok(resp) if resp.status().is_success() => {
+                log::info!("Backend ready after {:?} ({} attempts)", start.elapsed(), attempt);
+                emit_backend_status(app, "ready", attempt, start.elapsed());
+                return true;
+            }
+            _ => {
+                emit_backend_status(app, "starting", attempt, start.elapsed());
+                if start.elapsed() >= STARTUP_TIMEOUT {
+                    log::warn!("Backend did not become ready within {:?}", STARTUP_TIMEOUT);
+                    emit_backend_status(app, "failed", attempt, start.elapsed());
+                    return false;
+                }
+                let jitter = std::time::Duration::from_millis(rand::random::<u64>() % 100);
+                std::thread::sleep(delay + jitter);
+                delay = std::cmp::min(delay * 2, BACKOFF_CAP);
+            }
+        }
+    }
+}
+
+// Runs for the app's lifetime: brings the backend up, then keeps polling
+// its health and restarting it through spawn_backend when needed.
+fn monitor_backend(app: tauri::AppHandle, backend_path: PathBuf, port: u16) {
     let client = reqwest::blocking::Client::builder()
         .timeout(std::time::Duration::from_secs(2))
         .build()
         .unwrap();
+    let url = format!("http://localhost:{}/api/status", port);
+    let mut restart_delay = BACKOFF_START;
 
-    for i in 0..30 {
-        match client.get(url).send() {
-            Ok(resp) if resp.status().is_success() => {
-                log::info!("Backend ready after {}s", i);
-                return;
+    loop {
+        let start = std::time::Instant::now();
+        if !poll_until_ready(&app, &client, &url, start) {
+            // Startup failed or the restart below never actually got the
+            // backend running; keep retrying with backoff rather than
+            // ending monitoring (and all future auto-restarts) for good.
+            log::warn!("Retrying backend startup in {:?}", restart_delay);
+            std::thread::sleep(restart_delay);
+            restart_delay = std::cmp::min(restart_delay * 2, RESTART_BACKOFF_CAP);
+            restart_backend(&app, &backend_path, port);
+            continue;
+        }
+        restart_delay = BACKOFF_START;
+
+        let mut consecutive_failures = 0u32;
+        loop {
+            std::thread::sleep(HEALTH_CHECK_INTERVAL);
+            match client.get(&url).send() {
+                Ok(resp) if resp.status().is_success() => {
+                    consecutive_failures = 0;
+                }
+                _ => {
+                    consecutive_failures += 1;
+                    if consecutive_failures >= UNHEALTHY_THRESHOLD {
+                        log::warn!("Backend stopped responding; restarting");
+                        emit_backend_status(&app, "unhealthy", consecutive_failures, start.elapsed());
+                        restart_backend(&app, &backend_path, port);
+                        break;
+                    }
+                }
             }
-            _ => {
-                std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+    }
+}
+
+// Kills the current backend (if any) and replaces it with a freshly
+// spawned one on the same port.
+fn restart_backend(app: &tauri::AppHandle, backend_path: &Path, port: u16) {
+    if let Some(state) = app.try_state::<BackendProcess>() {
+        if let Ok(mut guard) = state.0.lock() {
+            if let Some(old_handle) = guard.take() {
+                kill_backend_process_tree(old_handle);
             }
+            *guard = spawn_backend(app, backend_path, port);
         }
     }
-    log::warn!("Backend did not become ready within 30s");
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        .invoke_handler(tauri::generate_handler![notify_native])
+        .invoke_handler(tauri::generate_handler![
+            notify_native,
+            backend_port,
+            open_log_location
+        ])
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_opener::init())
         .setup(|app| {
-            if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
-                        .build(),
-                )?;
+            // Forward notification clicks/action-button presses into the
+            // webview so it can route them (e.g. focus a chat, open a tab).
+            {
+                use tauri_plugin_notification::NotificationExt;
+                let app_handle = app.handle().clone();
+                app.notification().on_action(move |event| {
+                    let _ = app_handle.emit(
+                        "notification://action",
+                        NotificationActionEvent {
+                            notification_id: event.id(),
+                            action_id: event.action_id().map(|id| id.to_string()),
+                        },
+                    );
+                });
             }
 
+            // Always log to stdout + a rotating file, even in release builds.
+            let log_level = if cfg!(debug_assertions) {
+                log::LevelFilter::Info
+            } else {
+                log::LevelFilter::Warn
+            };
+            app.handle().plugin(
+                tauri_plugin_log::Builder::default()
+                    .level(log_level)
+                    .target(tauri_plugin_log::Target::new(
+                        tauri_plugin_log::TargetKind::Stdout,
+                    ))
+                    .target(tauri_plugin_log::Target::new(
+                        tauri_plugin_log::TargetKind::LogDir {
+                            file_name: Some("krith".into()),
+                        },
+                    ))
+                    .max_file_size(10_000_000) // 10MB before rotating
+                    // KeepAll never deletes rotated files; KeepOne deletes
+                    // the full log file on rotation, so only one file is kept.
+                    .rotation_strategy(tauri_plugin_log::RotationStrategy::KeepOne)
+                    .build(),
+            )?;
+
             // Spawn the Python backend server
             let resource_dir = app.path().resource_dir().unwrap_or_default();
 
@@ -196,16 +494,19 @@ pub fn run() {
                 resolve_release_backend_path(&resource_dir)
             };
 
-            log::info!("Starting backend from: {:?}", backend_path);
+            let port = reserve_ephemeral_port().unwrap_or(8484);
+            log::info!("Starting backend from: {:?} on port {}", backend_path, port);
 
-            let child = spawn_backend(&backend_path);
+            let child = spawn_backend(&app.handle().clone(), &backend_path, port);
             let has_child = child.is_some();
             app.manage(BackendProcess(Mutex::new(child)));
+            app.manage(BackendPort(port));
 
-            // Poll for backend readiness only if we launched a child.
+            // Monitor readiness and health only if we launched a child.
             if has_child {
-                std::thread::spawn(|| {
-                    wait_for_backend_ready();
+                let app_handle = app.handle().clone();
+                std::thread::spawn(move || {
+                    monitor_backend(app_handle, backend_path, port);
                 });
             } else {
                 log::warn!("Backend process was not started by Tauri.");
@@ -215,12 +516,14 @@ pub fn run() {
         })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::Destroyed = event {
-                // Kill backend when app closes
+                // Kill backend when app closes. The grace-period wait in
+                // kill_backend_process_tree blocks for up to a few seconds,
+                // so it must not run on this (UI event loop) thread.
                 if let Some(state) = window.try_state::<BackendProcess>() {
                     if let Ok(mut guard) = state.0.lock() {
-                        if let Some(child) = guard.as_mut() {
+                        if let Some(handle) = guard.take() {
                             log::info!("Shutting down backend server...");
-                            let _ = child.kill();
+                            std::thread::spawn(move || kill_backend_process_tree(handle));
                         }
                     }
                 }